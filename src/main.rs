@@ -1,7 +1,9 @@
 use anyhow::{Context, Result};
 use clap::{ArgAction, Parser, ValueHint};
 use ffmpeg_cli::{FfmpegBuilder, File as FfmpegFile, Parameter};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tempfile::tempdir;
 use tokio::fs;
 use which::which;
@@ -33,7 +35,7 @@ struct Cli {
     #[arg(long)]
     start: Option<String>,
 
-    /// Optional duration (e.g., 10 for 10 seconds; or 00:00:10)
+    /// Duration (e.g., 10 for 10 seconds; or 00:00:10). Required when --live is set.
     #[arg(long)]
     duration: Option<String>,
 
@@ -48,15 +50,102 @@ struct Cli {
     /// Force download of yt-dlp if not present
     #[arg(long, action = ArgAction::SetTrue)]
     fetch_yt_dlp: bool,
+
+    /// Slice a playlist (yt-dlp syntax, e.g. "1,3,5-10"). Only applies when the URL is a playlist.
+    #[arg(long)]
+    playlist_items: Option<String>,
+
+    /// Cap the number of playlist entries downloaded
+    #[arg(long)]
+    max_downloads: Option<u64>,
+
+    /// Capture from an ongoing livestream instead of a finished VOD (requires --duration)
+    #[arg(long, action = ArgAction::SetTrue)]
+    live: bool,
+
+    /// Max retry attempts on transient yt-dlp failures (rate limiting, etc.)
+    #[arg(long, default_value_t = 3)]
+    retries: u32,
+
+    /// Initial backoff delay between retries, in seconds (doubles after each attempt)
+    #[arg(long, default_value_t = 2)]
+    retry_delay: u64,
+
+    /// Minimum free space (bytes) required beyond the estimated download + frame size
+    #[arg(long, default_value_t = 500_000_000)]
+    min_free_space: u64,
+
+    /// Cap source resolution by max height (e.g. 720, 1080). Ignored if --format is set.
+    #[arg(long)]
+    max_height: Option<u32>,
+
+    /// Raw yt-dlp format selector, overrides --max-height and the default selector
+    #[arg(long)]
+    format: Option<String>,
+
+    /// List available renditions via yt-dlp and exit, without downloading
+    #[arg(long, action = ArgAction::SetTrue)]
+    list_formats: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    ensure_ffmpeg_available()?;
+    if cli.live && cli.duration.is_none() {
+        anyhow::bail!("--duration is required when --live is set");
+    }
+
     ensure_yt_dlp_available(cli.fetch_yt_dlp).await?;
 
+    if cli.list_formats {
+        return list_formats(&cli.url).await;
+    }
+
+    ensure_ffmpeg_available()?;
+
+    let format = resolve_format_selector(cli.format.as_deref(), cli.max_height);
+
+    if !cli.live && resolve_is_playlist(&cli.url).await.context("resolving URL")? {
+        let (download_dir, _tmp_guard) = match cli.keep_video {
+            true => {
+                let stem = cli.video_path.file_stem().and_then(|s| s.to_str()).unwrap_or("video");
+                let parent = cli.video_path.parent().unwrap_or(Path::new("."));
+                (parent.join(format!("{stem}_playlist")), None)
+            }
+            false => {
+                let tmp = tempdir().context("create temp dir")?;
+                let path = tmp.path().join("playlist");
+                (path, Some(tmp))
+            }
+        };
+
+        check_free_space(&cli.url, &download_dir, &cli.out_dir, cli.fps, cli.duration.as_deref(), cli.max_downloads, cli.min_free_space)
+            .await.with_context(|| "pre-download free space check failed")?;
+
+        let videos = run_with_retries(
+            || download_playlist_as_mp4(&cli.url, &download_dir, cli.playlist_items.as_deref(), cli.max_downloads, &format),
+            cli.retries,
+            Duration::from_secs(cli.retry_delay),
+        ).await.with_context(|| "downloading playlist with yt-dlp failed")?;
+
+        for (label, video_path) in &videos {
+            let frame_out_dir = cli.out_dir.join(label);
+            extract_frames_with_ffmpeg(
+                video_path,
+                &frame_out_dir,
+                &cli.pattern,
+                cli.fps,
+                cli.scale.as_deref(),
+                cli.start.as_deref(),
+                cli.duration.as_deref(),
+            ).await.with_context(|| format!("ffmpeg frame extraction failed for {label}"))?;
+        }
+
+        println!(" Done. {} video(s) processed. Frames in: {}", videos.len(), cli.out_dir.display());
+        return Ok(());
+    }
+
     let (video_path, _tmp_guard) = match cli.keep_video {
         true => (cli.video_path.clone(), None),
         false => {
@@ -66,8 +155,15 @@ async fn main() -> Result<()> {
         }
     };
 
-    download_video_as_mp4(&cli.url, &video_path).await
-        .with_context(|| "downloading video with yt-dlp failed")?;
+    let temp_dir = video_path.parent().unwrap_or(Path::new("."));
+    check_free_space(&cli.url, temp_dir, &cli.out_dir, cli.fps, cli.duration.as_deref(), None, cli.min_free_space)
+        .await.with_context(|| "pre-download free space check failed")?;
+
+    run_with_retries(
+        || download_video_as_mp4(&cli.url, &video_path, cli.live, cli.duration.as_deref(), &format),
+        cli.retries,
+        Duration::from_secs(cli.retry_delay),
+    ).await.with_context(|| "downloading video with yt-dlp failed")?;
 
     extract_frames_with_ffmpeg(
         &video_path,
@@ -83,6 +179,231 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Probe the URL with `--flat-playlist` so we know whether to fan out over
+/// entries or just download a single video, without doing a real download yet.
+async fn resolve_is_playlist(url: &str) -> Result<bool> {
+    let mut probe = YoutubeDl::new(url);
+    probe.extra_arg("--flat-playlist").extra_arg("--dump-json");
+    match probe.run_async().await? {
+        YoutubeDlOutput::Playlist(_) => Ok(true),
+        YoutubeDlOutput::SingleVideo(_) => Ok(false),
+    }
+}
+
+/// Extensions yt-dlp may produce for the media itself, as opposed to `.part`/`.ytdl`/
+/// `.info.json` sidecar files left behind in the same directory.
+const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "mkv", "webm", "mov", "avi"];
+
+/// Download every entry of a playlist into `dir`, named `<playlist_index>-<id>.<ext>`
+/// by yt-dlp's output template, and return each file alongside the subfolder label
+/// its frames should land in.
+async fn download_playlist_as_mp4(
+    url: &str,
+    dir: &Path,
+    playlist_items: Option<&str>,
+    max_downloads: Option<u64>,
+    format: &str,
+) -> Result<Vec<(String, PathBuf)>> {
+    fs::create_dir_all(dir).await.context("creating playlist download directory")?;
+    let template = dir.join("%(playlist_index)s-%(id)s.%(ext)s");
+
+    let mut ytdl = YoutubeDl::new(url);
+    ytdl
+        .extra_arg("-o").extra_arg(template.to_string_lossy())
+        .extra_arg("-f").extra_arg(format)
+        .extra_arg("--remux-video").extra_arg("mp4");
+
+    if let Some(items) = playlist_items {
+        ytdl.extra_arg("--playlist-items").extra_arg(items);
+    }
+    if let Some(max) = max_downloads {
+        ytdl.extra_arg("--max-downloads").extra_arg(max.to_string());
+    }
+
+    match ytdl.run_async().await {
+        Ok(_) => {}
+        // yt-dlp exits non-zero (code 101) once --max-downloads caps the run; the files
+        // downloaded so far are still valid, so that's a success, not a failure.
+        Err(youtube_dl::Error::ExitCode { code: 101, .. }) if max_downloads.is_some() => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    let mut videos = Vec::new();
+    let mut entries = fs::read_dir(dir).await.context("reading playlist download directory")?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let is_video = path.is_file()
+            && path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if is_video {
+            let label = path.file_stem().and_then(|s| s.to_str()).unwrap_or("video").to_string();
+            videos.push((label, path));
+        }
+    }
+    videos.sort();
+
+    if videos.is_empty() {
+        anyhow::bail!("yt-dlp did not produce any files in {}", dir.display());
+    }
+    Ok(videos)
+}
+
+/// Build the yt-dlp format selector: a raw `--format` passthrough wins outright, otherwise
+/// `--max-height` caps the default mp4-preferring selector, otherwise it's unconstrained.
+fn resolve_format_selector(format: Option<&str>, max_height: Option<u32>) -> String {
+    if let Some(format) = format {
+        return format.to_string();
+    }
+    match max_height {
+        Some(h) => format!("bv*[ext=mp4][height<={h}]+ba[ext=m4a]/b[ext=mp4][height<={h}]/best"),
+        None => "bv*[ext=mp4]+ba[ext=m4a]/b[ext=mp4]/best".to_string(),
+    }
+}
+
+/// Print available renditions via `yt-dlp --list-formats` without downloading anything.
+async fn list_formats(url: &str) -> Result<()> {
+    let status = tokio::process::Command::new("yt-dlp")
+        .arg("--list-formats")
+        .arg(url)
+        .status()
+        .await
+        .context("running yt-dlp --list-formats")?;
+    if !status.success() {
+        anyhow::bail!("yt-dlp --list-formats exited with {status}");
+    }
+    Ok(())
+}
+
+/// Rough average PNG size (bytes), used only to estimate frame-extraction disk usage.
+const ESTIMATED_BYTES_PER_FRAME: u64 = 300_000;
+
+/// Probe the video's metadata (no download) and make sure both the download directory
+/// and the frame output directory have enough free space for the download plus the
+/// estimated `fps * duration` PNGs. Aborts early rather than leaving half-written output.
+async fn check_free_space(
+    url: &str,
+    download_dir: &Path,
+    out_dir: &Path,
+    fps: u32,
+    duration: Option<&str>,
+    max_downloads: Option<u64>,
+    min_free_space: u64,
+) -> Result<()> {
+    let mut probe = YoutubeDl::new(url);
+    probe.extra_arg("--dump-json");
+    let info = probe.run_async().await.context("probing video metadata")?;
+
+    let videos = match info {
+        YoutubeDlOutput::SingleVideo(video) => vec![*video],
+        YoutubeDlOutput::Playlist(playlist) => {
+            let entries = playlist.entries.unwrap_or_default();
+            let take = max_downloads.map_or(entries.len(), |n| n as usize);
+            entries.into_iter().take(take).collect()
+        }
+    };
+    if videos.is_empty() {
+        anyhow::bail!("playlist has no entries to estimate size from");
+    }
+
+    let mut video_bytes = 0u64;
+    let mut estimated_frame_bytes = 0u64;
+    let mut missing_estimate = false;
+
+    for video in &videos {
+        let duration_secs = duration
+            .and_then(|d| d.split(':').try_fold(0.0, |acc, part| part.parse::<f64>().ok().map(|v| acc * 60.0 + v)))
+            .or_else(|| video.duration.as_ref().and_then(parse_duration_value))
+            .unwrap_or(0.0);
+
+        video_bytes += match video.filesize.or(video.filesize_approx) {
+            Some(bytes) => bytes as u64,
+            // No filesize reported (common for merged formats) - fall back to
+            // bitrate (kbps) * duration, and only give up entirely if we have neither.
+            None => match video.tbr {
+                Some(kbps) if duration_secs > 0.0 => (kbps * 1000.0 / 8.0 * duration_secs) as u64,
+                _ => {
+                    missing_estimate = true;
+                    0
+                }
+            },
+        };
+        estimated_frame_bytes += (fps as f64 * duration_secs * ESTIMATED_BYTES_PER_FRAME as f64) as u64;
+    }
+
+    if missing_estimate {
+        eprintln!(
+            "warning: yt-dlp reported no filesize or bitrate for one or more videos; \
+             the download free-space check for those falls back to just --min-free-space"
+        );
+    }
+
+    fs::create_dir_all(download_dir).await.ok();
+    fs::create_dir_all(out_dir).await.ok();
+
+    let download_free = fs2::available_space(download_dir)
+        .with_context(|| format!("checking free space on {}", download_dir.display()))?;
+    if download_free < video_bytes + min_free_space {
+        anyhow::bail!(
+            "not enough free space in {}: {} bytes free, need ~{} bytes for the download (use --min-free-space to adjust the margin)",
+            download_dir.display(), download_free, video_bytes + min_free_space
+        );
+    }
+
+    let out_free = fs2::available_space(out_dir)
+        .with_context(|| format!("checking free space on {}", out_dir.display()))?;
+    if out_free < estimated_frame_bytes + min_free_space {
+        anyhow::bail!(
+            "not enough free space in {}: {} bytes free, need ~{} bytes for the extracted frames (use --min-free-space to adjust the margin)",
+            out_dir.display(), out_free, estimated_frame_bytes + min_free_space
+        );
+    }
+
+    Ok(())
+}
+
+/// yt-dlp sometimes reports `duration` as a JSON number and sometimes as a numeric string.
+fn parse_duration_value(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Retry `attempt_fn` on yt-dlp failures that look transient (rate limiting, "technical
+/// difficulties"), with exponential backoff. Any other error is returned immediately.
+async fn run_with_retries<F, Fut, T>(mut attempt_fn: F, retries: u32, retry_delay: Duration) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut delay = retry_delay;
+    for attempt in 0..=retries {
+        match attempt_fn().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < retries && is_transient_yt_dlp_error(&err) => {
+                eprintln!(
+                    "yt-dlp hit a transient error, retrying in {delay:?} (attempt {}/{retries}): {err}",
+                    attempt + 1,
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!("loop always returns by the final attempt")
+}
+
+/// Inspect yt-dlp's stderr for substrings that indicate a transient, retryable failure.
+fn is_transient_yt_dlp_error(err: &anyhow::Error) -> bool {
+    let Some(youtube_dl::Error::ExitCode { stderr, .. }) = err.downcast_ref::<youtube_dl::Error>() else {
+        return false;
+    };
+    let stderr = stderr.to_lowercase();
+    ["429", "too many request", "technical difficult"]
+        .iter()
+        .any(|needle| stderr.contains(needle))
+}
+
 fn ensure_ffmpeg_available() -> Result<()> {
     which("ffmpeg").context(
         "ffmpeg not found on PATH. ",
@@ -113,20 +434,37 @@ async fn ensure_yt_dlp_available(fetch_if_missing: bool) -> Result<()> {
     Ok(())
 }
 
-async fn download_video_as_mp4(url: &str, output_path: &Path) -> Result<PathBuf> {
+async fn download_video_as_mp4(
+    url: &str,
+    output_path: &Path,
+    live: bool,
+    duration: Option<&str>,
+    format: &str,
+) -> Result<PathBuf> {
     if let Some(parent) = output_path.parent() {
         fs::create_dir_all(parent).await.ok();
     }
 
-    //ask yt-dlp for mp4 if possible, falling back to best.
-    //   yt-dlp -o <output> -f "bv*[ext=mp4]+ba[ext=m4a]/b[ext=mp4]/best" --remux-video mp4 <URL>
+    //ask yt-dlp for the requested quality, falling back to best.
+    //   yt-dlp -o <output> -f <format> --remux-video mp4 <URL>
     let mut ytdl = YoutubeDl::new(url);
     ytdl
         .extra_arg("-o").extra_arg(output_path.to_string_lossy())
-        .extra_arg("-f").extra_arg("bv*[ext=mp4]+ba[ext=m4a]/b[ext=mp4]/best")
+        .extra_arg("-f").extra_arg(format)
         .extra_arg("--remux-video").extra_arg("mp4");
 
-    let out: YoutubeDlOutput = ytdl.run_async().await?;
+    if live {
+        // The native HLS downloader only fetches the first chunk of a live .m3u8 and
+        // stops, so force the ffmpeg-based HLS downloader and tell it when to quit.
+        // --live requires --duration; main() validates that before we get here.
+        let duration = duration.context("--duration is required with --live")?;
+        ytdl
+            .extra_arg("--hls-use-mpegts")
+            .extra_arg("--downloader").extra_arg("m3u8:ffmpeg")
+            .extra_arg("--downloader-args").extra_arg(format!("ffmpeg:-t {duration}"));
+    }
+
+    ytdl.run_async().await?;
 
     if !output_path.exists() {
 